@@ -0,0 +1,65 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Buffers mapped rows across consecutive checkpoints so [`crate::indexer_builder::Persistent`]
+//! can be written to in batches instead of once per checkpoint, which is what
+//! [`crate::indexer_builder::Datasource`] implementations naturally produce.
+
+use std::time::{Duration, Instant};
+
+/// Default number of rows to buffer before flushing.
+pub const DEFAULT_BATCH_SIZE: usize = 5000;
+
+/// Default debounce: flush even a partial batch after this much time has passed.
+pub const DEFAULT_BATCH_MAX_DELAY: Duration = Duration::from_secs(1);
+
+/// Accumulates mapped rows `R` across checkpoints, tracking the highest checkpoint whose
+/// rows are currently buffered so that a flush can report exactly which checkpoint the
+/// flushed data is complete up to.
+pub struct Batcher<R> {
+    batch_size: usize,
+    max_delay: Duration,
+    buffer: Vec<R>,
+    last_flush: Instant,
+    highest_checkpoint: Option<u64>,
+}
+
+impl<R> Batcher<R> {
+    pub fn new(batch_size: usize, max_delay: Duration) -> Self {
+        Self {
+            batch_size,
+            max_delay,
+            buffer: Vec::new(),
+            last_flush: Instant::now(),
+            highest_checkpoint: None,
+        }
+    }
+
+    /// Buffers `rows` produced for `checkpoint`. Checkpoints must be pushed in increasing
+    /// order, matching the order a single [`Datasource`](crate::indexer_builder::Datasource)
+    /// delivers them in. Returns the batch to flush if the max batch size has been reached.
+    pub fn push(&mut self, checkpoint: u64, mut rows: Vec<R>) -> Option<(Vec<R>, u64)> {
+        self.buffer.append(&mut rows);
+        self.highest_checkpoint = Some(checkpoint);
+        if self.buffer.len() >= self.batch_size {
+            return self.take();
+        }
+        None
+    }
+
+    /// Whether the debounce timer has elapsed since the last flush and there is data buffered.
+    pub fn is_due(&self) -> bool {
+        !self.buffer.is_empty() && self.last_flush.elapsed() >= self.max_delay
+    }
+
+    /// Flushes whatever is currently buffered, if anything, resetting the debounce timer.
+    pub fn take(&mut self) -> Option<(Vec<R>, u64)> {
+        let checkpoint = self.highest_checkpoint.take()?;
+        self.last_flush = Instant::now();
+        Some((std::mem::take(&mut self.buffer), checkpoint))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+}
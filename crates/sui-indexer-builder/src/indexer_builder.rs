@@ -0,0 +1,362 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::batching::{Batcher, DEFAULT_BATCH_MAX_DELAY, DEFAULT_BATCH_SIZE};
+use crate::Task;
+
+/// Channel used by a [`Datasource`] to hand mapped checkpoint data back to the indexer
+/// driving it. Each message carries the checkpoint number together with the rows produced
+/// for it, so downstream consumers can commit progress that matches exactly what was written.
+pub type DataSender<T> = mpsc::Sender<(u64, Vec<T>)>;
+
+/// A source of raw checkpoint data, e.g. a fullnode GraphQL/gRPC client or a test fixture.
+#[async_trait]
+pub trait Datasource<T>: Send + Sync {
+    /// Starts streaming data from `starting_checkpoint` up to (but not including)
+    /// `target_checkpoint` into `data_sender`, returning a handle to the background task.
+    async fn start_data_retrieval(
+        &self,
+        starting_checkpoint: u64,
+        target_checkpoint: u64,
+        data_sender: DataSender<T>,
+    ) -> Result<JoinHandle<Result<(), Error>>, Error>;
+
+    /// The checkpoint at which the live (non-backfill) task should start.
+    async fn get_live_task_starting_checkpoint(&self) -> Result<u64, Error>;
+
+    /// The lowest checkpoint this datasource can serve, used as the floor for backfills.
+    fn get_genesis_height(&self) -> u64;
+}
+
+/// Transforms raw datasource data `T` into the rows `R` that get passed to [`Persistent::write`].
+pub trait DataMapper<T, R>: Send + Sync + Clone {
+    fn map(&self, data: T) -> Result<Vec<R>, Error>;
+}
+
+/// Tracks the set of indexing tasks (live and backfill) and how far each has progressed.
+#[async_trait]
+pub trait IndexerProgressStore: Send {
+    async fn load_progress(&self, task_name: String) -> Result<u64, Error>;
+
+    async fn save_progress(
+        &mut self,
+        task_name: String,
+        checkpoint_number: u64,
+    ) -> Result<(), Error>;
+
+    async fn get_ongoing_tasks(&self, task_prefix: &str) -> Result<Vec<Task>, Error>;
+
+    async fn get_largest_backfill_task_target_checkpoint(
+        &self,
+        task_prefix: &str,
+    ) -> Result<Option<u64>, Error>;
+
+    async fn register_task(
+        &mut self,
+        task_name: String,
+        checkpoint: u64,
+        target_checkpoint: u64,
+    ) -> Result<(), Error>;
+
+    async fn update_task(&mut self, task: Task) -> Result<(), Error>;
+
+    /// Gives a store that buffers progress updates (e.g. [`ThrottledProgressStore`](crate::progress_store::ThrottledProgressStore))
+    /// a chance to flush purely on the passage of time, independent of `save_progress`/`commit`
+    /// calls. [`Indexer`] invokes this on every tick of its batch debounce timer, so a task that
+    /// stops producing checkpoints (it's caught up, or reached its target) still gets its
+    /// pending progress flushed promptly instead of only on `shutdown`. The default is a no-op,
+    /// which is correct for stores that persist immediately.
+    async fn tick(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Forces any progress buffered by a throttling wrapper to be written out immediately.
+    /// [`Indexer`] calls this once its datasource has finished sending for a task, so a
+    /// graceful end-of-stream always leaves the recorded checkpoint caught up with the data
+    /// already committed, rather than relying on the next `tick` or an idempotent replay after
+    /// a restart. The default is a no-op, which is correct for stores that persist immediately.
+    async fn shutdown(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Returns tasks matching `filter`, sorted by checkpoint descending. More general than
+    /// [`IndexerProgressStore::get_ongoing_tasks`], which only supports prefix matching.
+    async fn get_tasks(&self, filter: TaskFilter) -> Result<Vec<Task>, Error>;
+
+    /// Returns the task with the lowest `global_id` among those with `task_name` starting
+    /// with `prefix` that still have work left (`checkpoint < target_checkpoint`), giving
+    /// operators a deterministic, gap-free order to replay many historical ranges in rather
+    /// than the ad-hoc checkpoint-descending order [`IndexerProgressStore::get_ongoing_tasks`]
+    /// returns.
+    async fn next_pending_task(&self, prefix: &str) -> Result<Option<Task>, Error> {
+        let mut pending = self
+            .get_tasks(
+                TaskFilter::new()
+                    .with_task_name_prefix(prefix)
+                    .with_predicate(|task| task.checkpoint < task.target_checkpoint),
+            )
+            .await?;
+        pending.sort_by_key(|task| task.global_id);
+        Ok(pending.into_iter().next())
+    }
+}
+
+/// A restriction on which [`Task`]s a [`IndexerProgressStore::get_tasks`] query should return.
+/// All set fields must match (AND semantics); leave a field `None`/empty to not filter on it.
+#[derive(Default)]
+pub struct TaskFilter {
+    /// Only tasks whose name starts with this prefix.
+    pub task_name_prefix: Option<String>,
+    /// Only tasks whose `checkpoint` falls in this range.
+    pub checkpoint_range: Option<std::ops::Range<u64>>,
+    /// Only tasks whose `target_checkpoint` falls in this range.
+    pub target_checkpoint_range: Option<std::ops::Range<u64>>,
+    /// Only tasks registered within this range (milliseconds since the Unix epoch).
+    pub timestamp_range: Option<std::ops::Range<u64>>,
+    /// Restrict to only backfill tasks, only live tasks, or leave unrestricted.
+    pub task_kind: Option<TaskKind>,
+    /// An additional caller-supplied predicate, applied after all other constraints.
+    pub predicate: Option<Box<dyn Fn(&Task) -> bool + Send + Sync>>,
+}
+
+impl TaskFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_task_name_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.task_name_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn with_checkpoint_range(mut self, range: std::ops::Range<u64>) -> Self {
+        self.checkpoint_range = Some(range);
+        self
+    }
+
+    pub fn with_target_checkpoint_range(mut self, range: std::ops::Range<u64>) -> Self {
+        self.target_checkpoint_range = Some(range);
+        self
+    }
+
+    pub fn with_timestamp_range(mut self, range: std::ops::Range<u64>) -> Self {
+        self.timestamp_range = Some(range);
+        self
+    }
+
+    pub fn with_task_kind(mut self, kind: TaskKind) -> Self {
+        self.task_kind = Some(kind);
+        self
+    }
+
+    pub fn with_predicate(
+        mut self,
+        predicate: impl Fn(&Task) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.predicate = Some(Box::new(predicate));
+        self
+    }
+
+    /// Returns whether `task` satisfies every constraint set on this filter.
+    pub fn matches(&self, task: &Task) -> bool {
+        if let Some(prefix) = &self.task_name_prefix {
+            if !task.task_name.starts_with(prefix.as_str()) {
+                return false;
+            }
+        }
+        if let Some(range) = &self.checkpoint_range {
+            if !range.contains(&task.checkpoint) {
+                return false;
+            }
+        }
+        if let Some(range) = &self.target_checkpoint_range {
+            if !range.contains(&task.target_checkpoint) {
+                return false;
+            }
+        }
+        if let Some(range) = &self.timestamp_range {
+            if !range.contains(&task.timestamp) {
+                return false;
+            }
+        }
+        match self.task_kind {
+            Some(TaskKind::Backfill) if task.is_live_task() => return false,
+            Some(TaskKind::Live) if !task.is_live_task() => return false,
+            _ => {}
+        }
+        if let Some(predicate) = &self.predicate {
+            if !predicate(task) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Selects live vs. backfill tasks in a [`TaskFilter`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TaskKind {
+    Backfill,
+    Live,
+}
+
+/// Builds an [`Indexer`] that drives a [`Datasource`] into a [`Persistent`] sink through a
+/// [`DataMapper`], auto-batching mapped rows between the two.
+pub struct IndexerBuilder<D, M, P> {
+    datasource: D,
+    data_mapper: M,
+    persistent: P,
+    batch_size: usize,
+    batch_max_delay: Duration,
+}
+
+impl<D, M, P> IndexerBuilder<D, M, P> {
+    pub fn new(datasource: D, data_mapper: M, persistent: P) -> Self {
+        Self {
+            datasource,
+            data_mapper,
+            persistent,
+            batch_size: DEFAULT_BATCH_SIZE,
+            batch_max_delay: DEFAULT_BATCH_MAX_DELAY,
+        }
+    }
+
+    /// Flush a batch once it reaches this many rows, even if the debounce timer hasn't fired.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Flush whatever is buffered after this much time has passed, even if `batch_size` rows
+    /// haven't accumulated yet.
+    pub fn with_batch_max_delay(mut self, batch_max_delay: Duration) -> Self {
+        self.batch_max_delay = batch_max_delay;
+        self
+    }
+
+    pub fn build(self) -> Indexer<D, M, P> {
+        Indexer {
+            datasource: self.datasource,
+            data_mapper: self.data_mapper,
+            persistent: self.persistent,
+            batch_size: self.batch_size,
+            batch_max_delay: self.batch_max_delay,
+        }
+    }
+}
+
+/// Drives a single [`Task`] to completion (or forever, for a live task): pulls data from
+/// `datasource`, maps it through `data_mapper`, batches the mapped rows, and commits each
+/// batch to `persistent` together with the checkpoint it is complete up to.
+pub struct Indexer<D, M, P> {
+    datasource: D,
+    data_mapper: M,
+    persistent: P,
+    batch_size: usize,
+    batch_max_delay: Duration,
+}
+
+impl<T, R, D, M, P> Indexer<D, M, P>
+where
+    T: Send + Sync + Clone + 'static,
+    R: Send,
+    D: Datasource<T>,
+    M: DataMapper<T, R>,
+    P: Persistent<R>,
+{
+    pub async fn run(&mut self, mut task: Task) -> Result<(), Error> {
+        let (tx, mut rx) = mpsc::channel(100);
+        let handle = self
+            .datasource
+            .start_data_retrieval(task.checkpoint, task.target_checkpoint, tx)
+            .await?;
+
+        let mut batcher = Batcher::<R>::new(self.batch_size, self.batch_max_delay);
+        let mut ticker = tokio::time::interval(self.batch_max_delay);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                maybe_msg = rx.recv() => {
+                    match maybe_msg {
+                        Some((checkpoint, rows)) => {
+                            let mapped = rows
+                                .into_iter()
+                                .map(|row| self.data_mapper.map(row))
+                                .collect::<Result<Vec<_>, _>>()?
+                                .into_iter()
+                                .flatten()
+                                .collect::<Vec<_>>();
+                            if let Some((batch, flushed_checkpoint)) = batcher.push(checkpoint, mapped) {
+                                self.flush(&mut task, batch, flushed_checkpoint).await?;
+                            }
+                        }
+                        // The datasource is done sending for this task; flush whatever
+                        // remains so a partial batch isn't silently dropped.
+                        None => {
+                            if let Some((batch, flushed_checkpoint)) = batcher.take() {
+                                self.flush(&mut task, batch, flushed_checkpoint).await?;
+                            }
+                            // Force out any progress a throttling wrapper is still sitting on,
+                            // so the recorded checkpoint is caught up before this task's driver
+                            // exits rather than left to the next tick or a restart.
+                            self.persistent.shutdown().await?;
+                            break;
+                        }
+                    }
+                }
+                _ = ticker.tick() => {
+                    if batcher.is_due() {
+                        if let Some((batch, flushed_checkpoint)) = batcher.take() {
+                            self.flush(&mut task, batch, flushed_checkpoint).await?;
+                        }
+                    }
+                    // Give a throttling progress store a chance to flush on elapsed time alone,
+                    // even while this task is idle and no batch is due.
+                    self.persistent.tick().await?;
+                }
+            }
+        }
+
+        handle.await??;
+        Ok(())
+    }
+
+    async fn flush(&mut self, task: &mut Task, batch: Vec<R>, checkpoint: u64) -> Result<(), Error> {
+        self.persistent
+            .commit(task.task_name.clone(), checkpoint, batch)
+            .await?;
+        task.checkpoint = checkpoint;
+        Ok(())
+    }
+}
+
+/// The durable sink that mapped rows are written to.
+///
+/// A `Persistent<T>` implementation is also an [`IndexerProgressStore`], since durably
+/// recording data and advancing the task cursor for it are two facets of the same commit.
+#[async_trait]
+pub trait Persistent<T: Send>: IndexerProgressStore + Send + Sync + Clone {
+    async fn write(&self, data: Vec<T>) -> Result<(), Error>;
+
+    /// Writes `data` and advances `task_name`'s checkpoint to `checkpoint`.
+    ///
+    /// The default implementation simply calls [`Persistent::write`] followed by
+    /// [`IndexerProgressStore::save_progress`], which is NOT atomic: a crash between the two
+    /// calls leaves the recorded checkpoint ahead of (or behind) the data actually persisted,
+    /// causing silent gaps or double-writes on restart. Backends that can express both as a
+    /// single transaction should override this method. The invariant `commit` must uphold is
+    /// that after it returns successfully, the stored checkpoint for `task_name` reflects
+    /// exactly the data that is durable - never more, never less.
+    async fn commit(&mut self, task_name: String, checkpoint: u64, data: Vec<T>) -> Result<(), Error> {
+        self.write(data).await?;
+        self.save_progress(task_name, checkpoint).await
+    }
+}
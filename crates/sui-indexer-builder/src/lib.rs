@@ -0,0 +1,31 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod batching;
+pub mod indexer_builder;
+pub mod postgres;
+pub mod progress_store;
+
+/// A unit of indexing work: a contiguous checkpoint range identified by `task_name`,
+/// running from `checkpoint` (inclusive, the next checkpoint to process) up to
+/// `target_checkpoint` (exclusive). A live task is one whose `target_checkpoint` is
+/// `i64::MAX as u64`, meaning it never completes and simply follows the chain tip.
+///
+/// `global_id` is assigned once, at registration time, in strictly increasing order across
+/// all tasks in a store. It defines the processing order
+/// [`IndexerProgressStore::next_pending_task`] hands tasks out in, independent of how far
+/// each has progressed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Task {
+    pub task_name: String,
+    pub checkpoint: u64,
+    pub target_checkpoint: u64,
+    pub timestamp: u64,
+    pub global_id: u64,
+}
+
+impl Task {
+    pub fn is_live_task(&self) -> bool {
+        self.target_checkpoint == i64::MAX as u64
+    }
+}
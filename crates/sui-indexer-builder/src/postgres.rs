@@ -0,0 +1,251 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A Postgres-backed implementation of [`IndexerProgressStore`] and [`Persistent`], so that
+//! standing up a durable indexer doesn't require hand-rolling a store the way
+//! [`InMemoryPersistent`](crate) (test-only) forces callers to. Connections are shared out of a
+//! bounded `deadpool_postgres` pool so concurrent backfill and live tasks don't each open their
+//! own connection to the database.
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use deadpool_postgres::{Config as PoolConfig, Pool, Runtime};
+use tokio_postgres::types::ToSql;
+use tokio_postgres::NoTls;
+
+use crate::indexer_builder::{IndexerProgressStore, Persistent, TaskFilter, TaskKind};
+use crate::Task;
+
+/// Turns `prefix` into a `LIKE` pattern that matches exactly the strings `prefix` is a prefix
+/// of - i.e. the SQL equivalent of `str::starts_with` - by escaping the `LIKE` wildcards `%`
+/// and `_` (and the escape character itself) before appending the trailing `%`. Every query
+/// built from this must include `ESCAPE '\'`.
+fn like_prefix_pattern(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for ch in prefix.chars() {
+        if matches!(ch, '\\' | '%' | '_') {
+            pattern.push('\\');
+        }
+        pattern.push(ch);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// A row type persisted through [`PgStore`] describes its own upsert, so `PgStore` never needs
+/// to know the target table's schema.
+pub trait Persist: Send + Sync {
+    /// The statement to run for one row, e.g. `INSERT INTO ... ON CONFLICT (...) DO UPDATE ...`.
+    /// Parameter placeholders (`$1`, `$2`, ...) must line up with [`Persist::params`].
+    fn upsert_sql(&self) -> &'static str;
+
+    fn params(&self) -> Vec<&(dyn ToSql + Sync)>;
+}
+
+/// A Postgres-backed [`IndexerProgressStore`] and [`Persistent`] implementation. The `tasks`
+/// table tracks `task_name`, `checkpoint`, `target_checkpoint`, `timestamp` and `global_id`,
+/// matching the fields on [`Task`]; see `migrations/0001_create_tasks_table.sql` and
+/// `migrations/0002_add_tasks_global_id.sql`.
+#[derive(Clone)]
+pub struct PgStore {
+    pool: Pool,
+}
+
+impl PgStore {
+    /// Connects to `database_url`, sizing the connection pool at `max_size`.
+    pub async fn connect(database_url: &str, max_size: usize) -> Result<Self, Error> {
+        let mut config = PoolConfig::new();
+        config.url = Some(database_url.to_string());
+        config.pool = Some(deadpool_postgres::PoolConfig::new(max_size));
+        let pool = config.create_pool(Some(Runtime::Tokio1), NoTls)?;
+        Ok(Self { pool })
+    }
+
+    /// Applies the bundled schema migrations, creating the `tasks` table if it doesn't
+    /// already exist. Safe to call on every startup.
+    pub async fn run_migrations(&self) -> Result<(), Error> {
+        let client = self.pool.get().await?;
+        client
+            .batch_execute(include_str!("../migrations/0001_create_tasks_table.sql"))
+            .await?;
+        client
+            .batch_execute(include_str!("../migrations/0002_add_tasks_global_id.sql"))
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl IndexerProgressStore for PgStore {
+    async fn load_progress(&self, task_name: String) -> Result<u64, Error> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_one(
+                "SELECT checkpoint FROM tasks WHERE task_name = $1",
+                &[&task_name],
+            )
+            .await?;
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    async fn save_progress(
+        &mut self,
+        task_name: String,
+        checkpoint_number: u64,
+    ) -> Result<(), Error> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE tasks SET checkpoint = $1 WHERE task_name = $2",
+                &[&(checkpoint_number as i64), &task_name],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_ongoing_tasks(&self, task_prefix: &str) -> Result<Vec<Task>, Error> {
+        let tasks = self
+            .get_tasks(TaskFilter::new().with_task_name_prefix(task_prefix))
+            .await?;
+        Ok(tasks
+            .into_iter()
+            .filter(|t| t.checkpoint < t.target_checkpoint)
+            .collect())
+    }
+
+    async fn get_largest_backfill_task_target_checkpoint(
+        &self,
+        task_prefix: &str,
+    ) -> Result<Option<u64>, Error> {
+        let client = self.pool.get().await?;
+        let row = client
+            .query_opt(
+                "SELECT MAX(target_checkpoint) FROM tasks \
+                 WHERE task_name LIKE $1 ESCAPE '\\' AND target_checkpoint != $2",
+                &[&like_prefix_pattern(task_prefix), &(i64::MAX)],
+            )
+            .await?;
+        Ok(row
+            .and_then(|r| r.get::<_, Option<i64>>(0))
+            .map(|v| v as u64))
+    }
+
+    async fn register_task(
+        &mut self,
+        task_name: String,
+        checkpoint: u64,
+        target_checkpoint: u64,
+    ) -> Result<(), Error> {
+        let client = self.pool.get().await?;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis() as i64;
+        let inserted = client
+            .execute(
+                "INSERT INTO tasks (task_name, checkpoint, target_checkpoint, timestamp) \
+                 VALUES ($1, $2, $3, $4) ON CONFLICT (task_name) DO NOTHING",
+                &[
+                    &task_name,
+                    &(checkpoint as i64),
+                    &(target_checkpoint as i64),
+                    &timestamp,
+                ],
+            )
+            .await?;
+        if inserted == 0 {
+            return Err(anyhow!("Task {task_name} already exists"));
+        }
+        Ok(())
+    }
+
+    async fn update_task(&mut self, task: Task) -> Result<(), Error> {
+        let client = self.pool.get().await?;
+        client
+            .execute(
+                "UPDATE tasks SET checkpoint = $1, target_checkpoint = $2, timestamp = $3 \
+                 WHERE task_name = $4",
+                &[
+                    &(task.checkpoint as i64),
+                    &(task.target_checkpoint as i64),
+                    &(task.timestamp as i64),
+                    &task.task_name,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_tasks(&self, filter: TaskFilter) -> Result<Vec<Task>, Error> {
+        // The range filters and caller predicate can't all be pushed into SQL generically, so
+        // only the indexed prefix/kind constraints are applied here; the rest run in memory.
+        let client = self.pool.get().await?;
+        let mut query =
+            "SELECT task_name, checkpoint, target_checkpoint, timestamp, global_id \
+             FROM tasks WHERE 1 = 1"
+                .to_string();
+        let mut params: Vec<Box<dyn ToSql + Sync + Send>> = vec![];
+        if let Some(prefix) = &filter.task_name_prefix {
+            params.push(Box::new(like_prefix_pattern(prefix)));
+            query.push_str(&format!(" AND task_name LIKE ${} ESCAPE '\\'", params.len()));
+        }
+        match filter.task_kind {
+            Some(TaskKind::Live) => {
+                params.push(Box::new(i64::MAX));
+                query.push_str(&format!(" AND target_checkpoint = ${}", params.len()));
+            }
+            Some(TaskKind::Backfill) => {
+                params.push(Box::new(i64::MAX));
+                query.push_str(&format!(" AND target_checkpoint != ${}", params.len()));
+            }
+            None => {}
+        }
+        query.push_str(" ORDER BY checkpoint DESC");
+
+        let params_ref: Vec<&(dyn ToSql + Sync)> =
+            params.iter().map(|p| p.as_ref() as &(dyn ToSql + Sync)).collect();
+        let rows = client.query(query.as_str(), &params_ref).await?;
+        let tasks = rows
+            .into_iter()
+            .map(|row| Task {
+                task_name: row.get(0),
+                checkpoint: row.get::<_, i64>(1) as u64,
+                target_checkpoint: row.get::<_, i64>(2) as u64,
+                timestamp: row.get::<_, i64>(3) as u64,
+                global_id: row.get::<_, i64>(4) as u64,
+            })
+            .filter(|task| filter.matches(task))
+            .collect();
+        Ok(tasks)
+    }
+}
+
+#[async_trait]
+impl<T: Persist + Clone + Send + Sync> Persistent<T> for PgStore {
+    async fn write(&self, data: Vec<T>) -> Result<(), Error> {
+        let mut client = self.pool.get().await?;
+        let tx = client.transaction().await?;
+        for row in &data {
+            tx.execute(row.upsert_sql(), &row.params()).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn commit(&mut self, task_name: String, checkpoint: u64, data: Vec<T>) -> Result<(), Error> {
+        // Run the row upserts and the checkpoint advance on the same connection, inside the
+        // same transaction, so a crash partway through never leaves the stored checkpoint
+        // ahead of (or behind) the rows that are actually durable.
+        let mut client = self.pool.get().await?;
+        let tx = client.transaction().await?;
+        for row in &data {
+            tx.execute(row.upsert_sql(), &row.params()).await?;
+        }
+        tx.execute(
+            "UPDATE tasks SET checkpoint = $1 WHERE task_name = $2",
+            &[&(checkpoint as i64), &task_name],
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
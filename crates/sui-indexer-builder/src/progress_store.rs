@@ -0,0 +1,219 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A throttling wrapper around [`IndexerProgressStore`] that bounds how often progress is
+//! actually persisted, since backends like Postgres pay a real cost per `save_progress` call
+//! and a naive per-checkpoint flush causes heavy write amplification.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Error;
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::indexer_builder::{IndexerProgressStore, Persistent, TaskFilter};
+use crate::Task;
+
+/// Flush at least this often, regardless of how many checkpoints have accumulated.
+pub const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Flush once this many checkpoints have accumulated since the last flush, even if
+/// `CHECKPOINT_INTERVAL` hasn't elapsed yet.
+pub const SAVE_STATE_EVERY: u64 = 1000;
+
+/// Never flush for fewer than this many new checkpoints, even if `CHECKPOINT_INTERVAL` has
+/// elapsed. Guards against a flush storm when checkpoints trickle in slowly.
+pub const CHECKPOINT_MIN_OPS: u64 = 1;
+
+/// The mutable throttling state, held behind a shared lock (see [`ThrottledProgressStore::state`])
+/// so that cloning the store - required by [`Persistent`]'s `Clone` bound - never duplicates
+/// buffered-but-unflushed progress across the clones.
+#[derive(Default)]
+struct ThrottleState {
+    /// The highest checkpoint observed per task since the last flush, pending persistence.
+    pending: HashMap<String, u64>,
+    last_flush: Option<Instant>,
+    ops_since_flush: u64,
+}
+
+/// Wraps any [`IndexerProgressStore`] and throttles how often `save_progress` actually reaches
+/// the inner store. Checkpoints are tracked locally and only flushed to `inner` when either
+/// `CHECKPOINT_INTERVAL` has elapsed since the last flush or `SAVE_STATE_EVERY` checkpoints have
+/// accumulated, and only once at least `CHECKPOINT_MIN_OPS` new checkpoints have been seen.
+///
+/// `CHECKPOINT_INTERVAL` is enforced both on `save_progress` and on [`IndexerProgressStore::tick`],
+/// so it behaves as a genuine wall-clock bound even while a task is idle and not producing new
+/// checkpoints - as long as something (e.g. [`Indexer`](crate::indexer_builder::Indexer)) is
+/// actually calling `tick` periodically. `load_progress` returns a task's pending (not yet
+/// flushed) checkpoint if there is one, and `shutdown` flushes everything pending; all other
+/// [`IndexerProgressStore`] methods pass straight through to `inner`.
+#[derive(Clone)]
+pub struct ThrottledProgressStore<S> {
+    inner: S,
+    checkpoint_interval: Duration,
+    save_state_every: u64,
+    checkpoint_min_ops: u64,
+    /// Shared so every clone of this store throttles against the same pending progress instead
+    /// of each accumulating (and independently flushing) its own copy.
+    state: Arc<Mutex<ThrottleState>>,
+}
+
+impl<S: IndexerProgressStore> ThrottledProgressStore<S> {
+    pub fn new(inner: S) -> Self {
+        Self::new_with_config(
+            inner,
+            CHECKPOINT_INTERVAL,
+            SAVE_STATE_EVERY,
+            CHECKPOINT_MIN_OPS,
+        )
+    }
+
+    pub fn new_with_config(
+        inner: S,
+        checkpoint_interval: Duration,
+        save_state_every: u64,
+        checkpoint_min_ops: u64,
+    ) -> Self {
+        Self {
+            inner,
+            checkpoint_interval,
+            save_state_every,
+            checkpoint_min_ops,
+            state: Arc::new(Mutex::new(ThrottleState::default())),
+        }
+    }
+
+    fn should_flush(&self, state: &ThrottleState) -> bool {
+        if state.ops_since_flush < self.checkpoint_min_ops {
+            return false;
+        }
+        state.ops_since_flush >= self.save_state_every
+            || match state.last_flush {
+                Some(last_flush) => last_flush.elapsed() >= self.checkpoint_interval,
+                None => true,
+            }
+    }
+
+    /// Drains whatever is pending and writes it through to `inner`, regardless of whether the
+    /// configured thresholds have actually been reached.
+    async fn flush(&mut self) -> Result<(), Error> {
+        let pending = {
+            let mut state = self.state.lock().await;
+            state.last_flush = Some(Instant::now());
+            state.ops_since_flush = 0;
+            std::mem::take(&mut state.pending)
+        };
+        for (task_name, checkpoint_number) in pending {
+            self.inner.save_progress(task_name, checkpoint_number).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S: IndexerProgressStore> IndexerProgressStore for ThrottledProgressStore<S> {
+    async fn load_progress(&self, task_name: String) -> Result<u64, Error> {
+        if let Some(checkpoint) = self.state.lock().await.pending.get(&task_name) {
+            return Ok(*checkpoint);
+        }
+        self.inner.load_progress(task_name).await
+    }
+
+    async fn save_progress(
+        &mut self,
+        task_name: String,
+        checkpoint_number: u64,
+    ) -> Result<(), Error> {
+        let should_flush = {
+            let mut state = self.state.lock().await;
+            state.pending.insert(task_name, checkpoint_number);
+            state.ops_since_flush += 1;
+            self.should_flush(&state)
+        };
+        if should_flush {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn get_ongoing_tasks(&self, task_prefix: &str) -> Result<Vec<Task>, Error> {
+        self.inner.get_ongoing_tasks(task_prefix).await
+    }
+
+    async fn get_largest_backfill_task_target_checkpoint(
+        &self,
+        task_prefix: &str,
+    ) -> Result<Option<u64>, Error> {
+        self.inner
+            .get_largest_backfill_task_target_checkpoint(task_prefix)
+            .await
+    }
+
+    async fn register_task(
+        &mut self,
+        task_name: String,
+        checkpoint: u64,
+        target_checkpoint: u64,
+    ) -> Result<(), Error> {
+        self.inner
+            .register_task(task_name, checkpoint, target_checkpoint)
+            .await
+    }
+
+    async fn update_task(&mut self, task: Task) -> Result<(), Error> {
+        self.inner.update_task(task).await
+    }
+
+    async fn get_tasks(&self, filter: TaskFilter) -> Result<Vec<Task>, Error> {
+        self.inner.get_tasks(filter).await
+    }
+
+    async fn tick(&mut self) -> Result<(), Error> {
+        // `should_flush`'s count-based branch was already checked on the last `save_progress`;
+        // re-checking here just lets the time-based branch fire even if no checkpoint has
+        // arrived since, so `CHECKPOINT_INTERVAL` is honored as a wall-clock bound rather than
+        // only advancing on the next incoming checkpoint.
+        let should_flush = self.should_flush(&*self.state.lock().await);
+        if should_flush {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Forces any pending progress to be written immediately, regardless of the configured
+    /// thresholds. Called by [`Indexer`](crate::indexer_builder::Indexer) once its datasource
+    /// is done sending for a task, so no progress made since the last flush is silently lost.
+    async fn shutdown(&mut self) -> Result<(), Error> {
+        self.flush().await
+    }
+}
+
+/// Lets a [`ThrottledProgressStore`] stand in for `S` wherever an [`Indexer`](crate::indexer_builder::Indexer)
+/// expects a [`Persistent`] - which is the only way progress actually advances in that driver,
+/// since it calls `commit`, never `save_progress`, directly.
+///
+/// This intentionally trades away chunk0-2's single-transaction atomicity for reduced write
+/// amplification: `data` is written through `inner` on every `commit` (never throttled, so no
+/// durable row is ever lost), but the checkpoint advance is throttled exactly like
+/// `save_progress` above. That means a crash between flushes can leave the recorded checkpoint
+/// *behind* data that is already durable - the opposite direction from the skew chunk0-2 closes,
+/// and safe only because `Persist` backends upsert (replaying already-durable checkpoints is
+/// idempotent). Backends that need commit's stronger guarantee should not be placed behind this
+/// wrapper.
+#[async_trait]
+impl<T, S> Persistent<T> for ThrottledProgressStore<S>
+where
+    T: Send,
+    S: Persistent<T> + IndexerProgressStore,
+{
+    async fn write(&self, data: Vec<T>) -> Result<(), Error> {
+        self.inner.write(data).await
+    }
+
+    async fn commit(&mut self, task_name: String, checkpoint: u64, data: Vec<T>) -> Result<(), Error> {
+        self.inner.write(data).await?;
+        self.save_progress(task_name, checkpoint).await
+    }
+}
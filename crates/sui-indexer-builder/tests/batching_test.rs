@@ -0,0 +1,63 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use sui_indexer_builder::batching::Batcher;
+
+#[test]
+fn flushes_once_batch_size_is_reached() {
+    let mut batcher = Batcher::<u64>::new(3, Duration::from_secs(60));
+
+    assert!(batcher.push(0, vec![1]).is_none());
+    assert!(batcher.push(1, vec![2]).is_none());
+    let (batch, checkpoint) = batcher.push(2, vec![3]).expect("batch size reached");
+
+    assert_eq!(batch, vec![1, 2, 3]);
+    assert_eq!(checkpoint, 2);
+    assert!(batcher.is_empty());
+}
+
+#[test]
+fn does_not_flush_before_batch_size_or_debounce() {
+    let mut batcher = Batcher::<u64>::new(10, Duration::from_secs(60));
+
+    assert!(batcher.push(0, vec![1]).is_none());
+    assert!(batcher.push(1, vec![2]).is_none());
+    assert!(!batcher.is_empty());
+    assert!(!batcher.is_due());
+}
+
+#[test]
+fn is_due_after_debounce_elapses() {
+    let mut batcher = Batcher::<u64>::new(10, Duration::from_millis(10));
+
+    batcher.push(0, vec![1]);
+    assert!(!batcher.is_due());
+
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(batcher.is_due());
+
+    let (batch, checkpoint) = batcher.take().expect("debounce elapsed, batch pending");
+    assert_eq!(batch, vec![1]);
+    assert_eq!(checkpoint, 0);
+}
+
+#[test]
+fn take_flushes_a_partial_batch_on_stream_end() {
+    let mut batcher = Batcher::<u64>::new(100, Duration::from_secs(60));
+
+    batcher.push(0, vec![1]);
+    batcher.push(1, vec![2, 3]);
+
+    let (batch, checkpoint) = batcher.take().expect("stream ended with a partial batch");
+    assert_eq!(batch, vec![1, 2, 3]);
+    assert_eq!(checkpoint, 1);
+    assert!(batcher.is_empty());
+}
+
+#[test]
+fn take_on_empty_batcher_returns_none() {
+    let mut batcher = Batcher::<u64>::new(10, Duration::from_secs(60));
+    assert!(batcher.take().is_none());
+}
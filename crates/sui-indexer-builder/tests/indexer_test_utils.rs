@@ -13,7 +13,7 @@ use tokio::task::JoinHandle;
 use mysten_metrics::spawn_monitored_task;
 
 use sui_indexer_builder::indexer_builder::{
-    DataMapper, DataSender, Datasource, IndexerProgressStore, Persistent,
+    DataMapper, DataSender, Datasource, IndexerProgressStore, Persistent, TaskFilter,
 };
 use sui_indexer_builder::Task;
 
@@ -61,6 +61,9 @@ where
 pub struct InMemoryPersistent<T> {
     pub progress_store: Arc<Mutex<HashMap<String, Task>>>,
     pub data: Arc<Mutex<Vec<T>>>,
+    /// The `global_id` to assign to the next registered task. Stored alongside `progress_store`
+    /// so it advances in lockstep with task registration.
+    next_task_id: Arc<Mutex<u64>>,
 }
 
 impl<T> InMemoryPersistent<T> {
@@ -68,6 +71,7 @@ impl<T> InMemoryPersistent<T> {
         InMemoryPersistent {
             progress_store: Default::default(),
             data: Arc::new(Mutex::new(vec![])),
+            next_task_id: Default::default(),
         }
     }
 
@@ -147,18 +151,23 @@ impl<T: Send + Sync> IndexerProgressStore for InMemoryPersistent<T> {
         checkpoint: u64,
         target_checkpoint: u64,
     ) -> Result<(), Error> {
-        let existing = self.progress_store.lock().await.insert(
+        let mut progress_store = self.progress_store.lock().await;
+        if progress_store.contains_key(&task_name) {
+            return Err(anyhow!("Task {task_name} already exists"));
+        }
+        let mut next_task_id = self.next_task_id.lock().await;
+        let global_id = *next_task_id;
+        progress_store.insert(
             task_name.clone(),
             Task {
                 task_name: task_name.clone(),
                 checkpoint,
                 target_checkpoint,
                 timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64,
+                global_id,
             },
         );
-        if existing.is_some() {
-            return Err(anyhow!("Task {task_name} already exists"));
-        }
+        *next_task_id += 1;
         Ok(())
     }
 
@@ -169,6 +178,19 @@ impl<T: Send + Sync> IndexerProgressStore for InMemoryPersistent<T> {
             .insert(task.task_name.clone(), task);
         Ok(())
     }
+
+    async fn get_tasks(&self, filter: TaskFilter) -> Result<Vec<Task>, Error> {
+        let mut tasks = self
+            .progress_store
+            .lock()
+            .await
+            .values()
+            .filter(|task| filter.matches(task))
+            .cloned()
+            .collect::<Vec<_>>();
+        tasks.sort_by(|t1, t2| t2.checkpoint.cmp(&t1.checkpoint));
+        Ok(tasks)
+    }
 }
 
 #[async_trait]
@@ -177,6 +199,24 @@ impl<T: Clone + Send + Sync> Persistent<T> for InMemoryPersistent<T> {
         self.data.lock().await.append(&mut data.clone());
         Ok(())
     }
+
+    async fn commit(
+        &mut self,
+        task_name: String,
+        checkpoint: u64,
+        mut data: Vec<T>,
+    ) -> Result<(), Error> {
+        // Take both locks together so a reader can never observe the data without the
+        // checkpoint that covers it, or vice versa.
+        let mut store_data = self.data.lock().await;
+        let mut progress_store = self.progress_store.lock().await;
+        let task = progress_store
+            .get_mut(&task_name)
+            .ok_or_else(|| anyhow!("Task {task_name} does not exist"))?;
+        store_data.append(&mut data);
+        task.checkpoint = checkpoint;
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
@@ -0,0 +1,69 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#[path = "indexer_test_utils.rs"]
+mod indexer_test_utils;
+
+use indexer_test_utils::InMemoryPersistent;
+use sui_indexer_builder::indexer_builder::IndexerProgressStore;
+
+#[tokio::test]
+async fn next_pending_task_returns_lowest_global_id_first() {
+    let mut store = InMemoryPersistent::<()>::new();
+
+    store
+        .register_task("backfill_b".to_string(), 0, 100)
+        .await
+        .unwrap();
+    store
+        .register_task("backfill_a".to_string(), 0, 100)
+        .await
+        .unwrap();
+    store
+        .register_task("backfill_c".to_string(), 0, 100)
+        .await
+        .unwrap();
+
+    // Registration order (b, a, c) must win over any other ordering, e.g. name or checkpoint.
+    let next = store
+        .next_pending_task("backfill_")
+        .await
+        .unwrap()
+        .expect("a pending task should be returned");
+    assert_eq!(next.task_name, "backfill_b");
+}
+
+#[tokio::test]
+async fn next_pending_task_skips_completed_tasks() {
+    let mut store = InMemoryPersistent::<()>::new();
+
+    store
+        .register_task("backfill_a".to_string(), 0, 100)
+        .await
+        .unwrap();
+    store
+        .register_task("backfill_b".to_string(), 0, 100)
+        .await
+        .unwrap();
+    store.save_progress("backfill_a".to_string(), 100).await.unwrap();
+
+    let next = store
+        .next_pending_task("backfill_")
+        .await
+        .unwrap()
+        .expect("backfill_b is still pending");
+    assert_eq!(next.task_name, "backfill_b");
+}
+
+#[tokio::test]
+async fn next_pending_task_returns_none_when_all_done() {
+    let mut store = InMemoryPersistent::<()>::new();
+
+    store
+        .register_task("backfill_a".to_string(), 0, 100)
+        .await
+        .unwrap();
+    store.save_progress("backfill_a".to_string(), 100).await.unwrap();
+
+    assert!(store.next_pending_task("backfill_").await.unwrap().is_none());
+}
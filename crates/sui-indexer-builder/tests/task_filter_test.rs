@@ -0,0 +1,82 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use sui_indexer_builder::indexer_builder::{TaskFilter, TaskKind};
+use sui_indexer_builder::Task;
+
+fn task(task_name: &str, checkpoint: u64, target_checkpoint: u64, timestamp: u64) -> Task {
+    Task {
+        task_name: task_name.to_string(),
+        checkpoint,
+        target_checkpoint,
+        timestamp,
+        global_id: 0,
+    }
+}
+
+#[test]
+fn matches_by_prefix() {
+    let filter = TaskFilter::new().with_task_name_prefix("backfill_");
+    assert!(filter.matches(&task("backfill_1", 0, 10, 0)));
+    assert!(!filter.matches(&task("live_1", 0, i64::MAX as u64, 0)));
+}
+
+#[test]
+fn matches_by_checkpoint_and_target_checkpoint_range() {
+    let filter = TaskFilter::new()
+        .with_checkpoint_range(5..10)
+        .with_target_checkpoint_range(20..30);
+    assert!(filter.matches(&task("t", 5, 20, 0)));
+    assert!(!filter.matches(&task("t", 4, 20, 0)));
+    assert!(!filter.matches(&task("t", 5, 30, 0)));
+}
+
+#[test]
+fn matches_by_timestamp_range() {
+    let filter = TaskFilter::new().with_timestamp_range(100..200);
+    assert!(filter.matches(&task("t", 0, 10, 150)));
+    assert!(!filter.matches(&task("t", 0, 10, 200)));
+    assert!(!filter.matches(&task("t", 0, 10, 99)));
+}
+
+#[test]
+fn matches_by_task_kind() {
+    let live = task("t", 5, i64::MAX as u64, 0);
+    let backfill = task("t", 5, 10, 0);
+
+    let only_live = TaskFilter::new().with_task_kind(TaskKind::Live);
+    assert!(only_live.matches(&live));
+    assert!(!only_live.matches(&backfill));
+
+    let only_backfill = TaskFilter::new().with_task_kind(TaskKind::Backfill);
+    assert!(only_backfill.matches(&backfill));
+    assert!(!only_backfill.matches(&live));
+}
+
+#[test]
+fn matches_by_custom_predicate() {
+    let filter = TaskFilter::new().with_predicate(|task| task.task_name.ends_with("_0"));
+    assert!(filter.matches(&task("shard_0", 0, 10, 0)));
+    assert!(!filter.matches(&task("shard_1", 0, 10, 0)));
+}
+
+#[test]
+fn all_constraints_must_match() {
+    let filter = TaskFilter::new()
+        .with_task_name_prefix("backfill_")
+        .with_checkpoint_range(0..10)
+        .with_task_kind(TaskKind::Backfill)
+        .with_predicate(|task| task.timestamp > 0);
+
+    assert!(filter.matches(&task("backfill_0", 5, 50, 10)));
+    // Fails the predicate only.
+    assert!(!filter.matches(&task("backfill_0", 5, 50, 0)));
+    // Fails the prefix only.
+    assert!(!filter.matches(&task("live_0", 5, 50, 10)));
+}
+
+#[test]
+fn empty_filter_matches_everything() {
+    let filter = TaskFilter::new();
+    assert!(filter.matches(&task("anything", 0, i64::MAX as u64, 0)));
+}